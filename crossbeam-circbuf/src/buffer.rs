@@ -1,13 +1,21 @@
-use std::cell::UnsafeCell;
-use std::sync::atomic::AtomicUsize;
-use std::sync::atomic::Ordering;
-use std::mem;
+use core::alloc::{Layout, LayoutError};
+use core::mem;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::sync::{AtomicUsize, Ordering, UnsafeCell};
 
 /// A slot in buffer.
+///
+/// `#[repr(C)]` gives this a fixed, well-defined field layout, which `from_raw`/`wrap` rely on:
+/// two processes (or the same source rebuilt by a different compiler version) mapping the same
+/// region as `[Slot<T>; cap]` must agree on where `index` and `data` live.
 #[derive(Debug)]
+#[repr(C)]
 pub struct Slot<T> {
     index: AtomicUsize,
-    data: UnsafeCell<mem::ManuallyDrop<T>>,
+    data: UnsafeCell<mem::MaybeUninit<T>>,
 }
 
 /// A buffer that holds values in a queue.
@@ -20,35 +28,136 @@ pub struct Buffer<T> {
 
     /// Capacity of the buffer. Always a power of two.
     cap: usize,
+
+    /// Whether `ptr` was allocated by this buffer (and so must be freed by it). `false` for
+    /// buffers created with `from_raw`/`wrap`, which overlay someone else's memory.
+    owned: bool,
 }
 
+// `Buffer<T>` is just an indirection to a `[Slot<T>]`: every access goes through `index`'s atomic
+// ops or an explicit volatile/word-wise copy of the `data` field, the same as a `T` held directly
+// in an `AtomicPtr`-style structure would. So `Buffer<T>` can cross threads, and be shared across
+// threads, under the same bound as `T` itself crossing threads: no access to a slot's `data` is
+// ever unsynchronized with another access to that same slot (that's the whole point of the
+// `index` protocol), so `Buffer<T>` doesn't need `T: Sync` the way a plain `&T` shared reference
+// would.
+unsafe impl<T: Send> Send for Buffer<T> {}
+unsafe impl<T: Send> Sync for Buffer<T> {}
+
 impl<T> Buffer<T> {
     /// Allocates a new buffer with the specified capacity.
     pub fn new(cap: usize) -> Self {
         // `cap` should be a power of two.
         debug_assert_eq!(cap, cap.next_power_of_two());
 
+        #[cfg(feature = "atomic-payload")]
+        assert_atomic_payload_layout::<T>();
+
         // Creates a buffer.
         let mut v = Vec::<Slot<T>>::with_capacity(cap);
         let ptr = v.as_mut_ptr();
         mem::forget(v);
 
-        // Marks all entries invalid.
-        unsafe {
-            for i in 0..cap {
-                // Index `i + 1` for the `i`-th entry is invalid; only the indexes of the form `i +
-                // N * cap` is valid.
-                (*ptr.offset(i as isize)).index = AtomicUsize::new(i + 1);
-            }
-        }
+        let buffer = Buffer { ptr, cap, owned: true };
+        unsafe { buffer.init_invalid() };
+        buffer
+    }
+
+    /// Creates a non-owning buffer over an already-initialized slot array.
+    ///
+    /// Unlike `new`, dropping the returned buffer does *not* free `ptr`; the caller keeps
+    /// ownership of the backing memory. This is meant for slot arrays that live in memory this
+    /// crate didn't allocate, e.g. a memory-mapped region shared with another process.
+    ///
+    /// `ptr` must already hold a valid slot array for this layout --- either because a previous
+    /// process initialized it via `Buffer::new`/`init_invalid` over the same shared memory, or
+    /// because the caller has called [`init_invalid`](Buffer::init_invalid) on the result before
+    /// letting any producer/consumer touch it. A freshly zeroed region is *not* on its own a
+    /// valid empty slot array: slot `0`'s zeroed `index` field is indistinguishable from a real
+    /// published value at index `0`, so skipping `init_invalid` makes the very first read of a
+    /// brand-new buffer return stale/garbage data instead of `None`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for reads and writes as `cap` consecutive `Slot<T>`s for as long as the
+    /// returned `Buffer` is in use, and `cap` must be a power of two.
+    pub unsafe fn from_raw(ptr: *mut Slot<T>, cap: usize) -> Self {
+        debug_assert_eq!(cap, cap.next_power_of_two());
 
-        Buffer { ptr, cap }
+        #[cfg(feature = "atomic-payload")]
+        assert_atomic_payload_layout::<T>();
+
+        Buffer { ptr, cap, owned: false }
+    }
+
+    /// Overlays a slot array for `cap` slots onto a caller-provided byte region, after validating
+    /// that `region` is large enough and suitably aligned for `Slot<T>`.
+    ///
+    /// As with `from_raw`, `region` must already hold a valid slot array --- see `from_raw`'s
+    /// docs for what that requires of a freshly zeroed region. `wrap` itself does not initialize
+    /// any slots.
+    ///
+    /// Like `from_raw`, dropping the returned buffer does not free `region`.
+    ///
+    /// # Safety
+    ///
+    /// `region` must be valid for reads and writes as a `[Slot<T>; cap]` for as long as the
+    /// returned `Buffer` is in use, and `cap` must be a power of two.
+    pub unsafe fn wrap(region: &mut [u8], cap: usize) -> Result<Self, LayoutError> {
+        debug_assert_eq!(cap, cap.next_power_of_two());
+
+        let layout = Layout::array::<Slot<T>>(cap)?;
+        assert!(
+            region.len() >= layout.size(),
+            "region of {} bytes is too small to hold {} slots ({} bytes)",
+            region.len(),
+            cap,
+            layout.size(),
+        );
+        assert_eq!(
+            region.as_mut_ptr() as usize % layout.align(),
+            0,
+            "region is not suitably aligned for Slot<T>",
+        );
+
+        Ok(Buffer::from_raw(region.as_mut_ptr() as *mut Slot<T>, cap))
+    }
+
+    /// Marks every slot as empty, by constructing each one in place with its invalid sentinel
+    /// index and an empty `data` cell.
+    ///
+    /// `Buffer::new` calls this itself. Callers of `from_raw`/`wrap` must call it once, before
+    /// any producer/consumer touches the buffer, if and only if the underlying region starts out
+    /// zeroed rather than already holding a valid slot array --- see `from_raw`'s docs.
+    ///
+    /// # Safety
+    ///
+    /// Must not be called concurrently with, or after, any `read`/`write`/`read_batch`/
+    /// `write_batch` on this buffer; it unconditionally overwrites every slot, without dropping
+    /// whatever was previously there (per the `from_raw`/`wrap` contract, that's never a live
+    /// value --- only ever zeroed or not-yet-initialized memory).
+    pub unsafe fn init_invalid(&self) {
+        for i in 0..self.cap {
+            // Index `i + 1` for the `i`-th entry is invalid; only the indexes of the form `i + N
+            // * cap` is valid.
+            core::ptr::write(
+                self.at(i),
+                Slot {
+                    index: AtomicUsize::new(i + 1),
+                    data: UnsafeCell::new(mem::MaybeUninit::uninit()),
+                },
+            );
+        }
     }
 }
 
 impl<T> Drop for Buffer<T> {
     fn drop(&mut self) {
-        unsafe { 
+        if !self.owned {
+            return;
+        }
+
+        unsafe {
             drop(Vec::from_raw_parts(self.ptr, 0, self.cap));
         }
     }
@@ -61,8 +170,8 @@ impl<T> Buffer<T> {
 
     /// Returns a pointer to the slot at the specified `index`.
     pub unsafe fn at(&self, index: usize) -> *mut Slot<T> {
-        // `array.size()` is always a power of two.
-        self.ptr.offset((index & (self.cap - 1)) as isize)
+        // `self.cap` is always a power of two.
+        self.ptr.add(index & (self.cap - 1))
     }
 
     /// Reads a value from the specified `index`.
@@ -72,6 +181,7 @@ impl<T> Buffer<T> {
     /// Using this concurrently with a `write` is technically speaking UB due to data races.  We
     /// should be using relaxed accesses, but that would cost too much performance.  Hence, as a
     /// HACK, we use volatile accesses instead.  Experimental evidence shows that this works.
+    #[cfg(not(feature = "atomic-payload"))]
     pub unsafe fn read(&self, index: usize) -> Option<mem::ManuallyDrop<T>> {
         let slot = self.at(index);
 
@@ -83,8 +193,35 @@ impl<T> Buffer<T> {
             return None;
         }
 
-        // Returns the value.
-        Some((*slot).data.get().read_volatile())
+        // Returns the value. `data` is a `*const MaybeUninit<T>`, which shares `T`'s layout, so
+        // the cast to `*const T` below is sound.
+        let value = (*slot).data.with(|data| (data as *const T).read_volatile());
+        Some(mem::ManuallyDrop::new(value))
+    }
+
+    /// Reads a value from the specified `index`.
+    ///
+    /// Returns `Some(v)` if `v` is at `index`; or `None` if there's no valid value for `index`.
+    ///
+    /// Unlike the default implementation, this copies the payload as a sequence of `usize` words
+    /// loaded with `Relaxed` after the `Acquire` load of `index`, instead of a `read_volatile` of
+    /// the whole value. This is free of the torn-read UB documented on the default `read`, at the
+    /// cost of requiring `T: Copy` with a size and alignment compatible with `usize` --- `T:
+    /// Copy` itself isn't checkable here without specialization, but the size/alignment
+    /// requirement is checked by [`assert_atomic_payload_layout`] in `Buffer::new`/`from_raw`.
+    #[cfg(feature = "atomic-payload")]
+    pub unsafe fn read(&self, index: usize) -> Option<mem::ManuallyDrop<T>> {
+        let slot = self.at(index);
+
+        // Reads the index with `Acquire`.
+        let i = (*slot).index.load(Ordering::Acquire);
+
+        // If the index in the buffer mismatches with the queried index, there's no valid value.
+        if index != i {
+            return None;
+        }
+
+        Some(mem::ManuallyDrop::new(read_words((*slot).data.get() as *const T)))
     }
 
     /// Reads a value from the specified `index` without checking the index.
@@ -94,11 +231,24 @@ impl<T> Buffer<T> {
     /// Using this concurrently with a `write` is technically speaking UB due to data races.  We
     /// should be using relaxed accesses, but that would cost too much performance.  Hence, as a
     /// HACK, we use volatile accesses instead.  Experimental evidence shows that this works.
+    #[cfg(not(feature = "atomic-payload"))]
+    pub unsafe fn read_unchecked(&self, index: usize) -> mem::ManuallyDrop<T> {
+        let slot = self.at(index);
+
+        // Returns the value. See `read`'s non-`atomic-payload` variant for why the cast is sound.
+        let value = (*slot).data.with(|data| (data as *const T).read_volatile());
+        mem::ManuallyDrop::new(value)
+    }
+
+    /// Reads a value from the specified `index` without checking the index.
+    ///
+    /// Returns the value at `index` regardless or whether it's valid or not. See the
+    /// `atomic-payload` variant of `read` for how the payload is copied.
+    #[cfg(feature = "atomic-payload")]
     pub unsafe fn read_unchecked(&self, index: usize) -> mem::ManuallyDrop<T> {
         let slot = self.at(index);
 
-        // Returns the value.
-        (*slot).data.get().read_volatile()
+        mem::ManuallyDrop::new(read_words((*slot).data.get() as *const T))
     }
 
     /// Writes `value` into the specified `index`.
@@ -107,13 +257,215 @@ impl<T> Buffer<T> {
     /// speaking UB due to data races.  We should be using relaxed accesses, but
     /// that would cost too much performance.  Hence, as a HACK, we use volatile
     /// accesses instead.  Experimental evidence shows that this works.
+    #[cfg(not(feature = "atomic-payload"))]
     pub unsafe fn write(&self, index: usize, value: T) {
         let slot = self.at(index);
 
-        // Writes the value.
-        (*slot).data.get().write_volatile(mem::ManuallyDrop::new(value));
+        // Writes the value. See `read`'s non-`atomic-payload` variant for why the cast is sound.
+        (*slot).data.with_mut(|data| (data as *mut T).write_volatile(value));
 
         // Writes the index with `Release`.
         (*slot).index.store(index, Ordering::Release);
     }
+
+    /// Writes `value` into the specified `index`.
+    ///
+    /// Unlike the default implementation, this copies the payload as a sequence of `usize` words
+    /// stored with `Relaxed` before the `Release` store of `index`, instead of a `write_volatile`
+    /// of the whole value. See the `atomic-payload` variant of `read` for the requirements on `T`
+    /// this relies on.
+    #[cfg(feature = "atomic-payload")]
+    pub unsafe fn write(&self, index: usize, value: T) {
+        let slot = self.at(index);
+
+        write_words((*slot).data.get() as *mut T, value);
+
+        // Writes the index with `Release`, publishing the words above.
+        (*slot).index.store(index, Ordering::Release);
+    }
+
+    /// Writes a contiguous run of `values` starting at `start_index` in one pass. Each slot's
+    /// `index` gets its own `Release` store, as in `write`; the run is not published by a single
+    /// store.
+    ///
+    /// `values` is drained: each element is moved out and left logically uninitialized, exactly
+    /// like `write` consuming its `value` argument.
+    ///
+    /// See [`read_batch`](Buffer::read_batch) for the matching consumer side. Wraparound is
+    /// handled per element by `at`, the same as `write`.
+    ///
+    /// Every slot's `index` is stored with `Release`, matching `write`'s single-slot protocol, so
+    /// a plain `read` on any slot of the run --- not just its last one --- still synchronizes with
+    /// the store that published it. `read_batch` only needs to check the last slot because it
+    /// additionally assumes the run is read as a whole; `read` makes no such assumption.
+    ///
+    /// # Safety
+    ///
+    /// Same as `write`: using this concurrently with a `read`/`write` touching the same indices
+    /// is technically speaking UB due to data races, for the same reasons documented there.
+    #[cfg(not(feature = "atomic-payload"))]
+    pub unsafe fn write_batch(&self, start_index: usize, values: &mut [mem::ManuallyDrop<T>]) {
+        for (i, value) in values.iter_mut().enumerate() {
+            let index = start_index.wrapping_add(i);
+            let slot = self.at(index);
+            let value = mem::ManuallyDrop::into_inner(core::ptr::read(value));
+
+            (*slot).data.with_mut(|data| (data as *mut T).write_volatile(value));
+            (*slot).index.store(index, Ordering::Release);
+        }
+    }
+
+    /// Writes a contiguous run of `values` starting at `start_index` in one pass. Each slot's
+    /// `index` gets its own `Release` store, as in `write`; the run is not published by a single
+    /// store.
+    ///
+    /// Unlike the default implementation, this copies each slot's payload as a sequence of
+    /// `usize` words instead of a `write_volatile`, the same tradeoff `write`'s `atomic-payload`
+    /// variant makes; see its docs for the requirements on `T` this relies on.
+    ///
+    /// # Safety
+    ///
+    /// Same as `write`'s `atomic-payload` variant: every payload word is stored atomically, so a
+    /// concurrent `read`/`read_batch` of the same indices is not a data race on `data`.
+    #[cfg(feature = "atomic-payload")]
+    pub unsafe fn write_batch(&self, start_index: usize, values: &mut [mem::ManuallyDrop<T>]) {
+        for (i, value) in values.iter_mut().enumerate() {
+            let index = start_index.wrapping_add(i);
+            let slot = self.at(index);
+            let value = mem::ManuallyDrop::into_inner(core::ptr::read(value));
+
+            write_words((*slot).data.get() as *mut T, value);
+            (*slot).index.store(index, Ordering::Release);
+        }
+    }
+
+    /// Reads a contiguous run of up to `out.len()` values starting at `start_index` in one pass,
+    /// validating the whole run with a single `Acquire` load on its last slot instead of one per
+    /// element.
+    ///
+    /// Returns the number of elements copied into `out`, starting from index `0`. This is either
+    /// `out.len()` if the whole run was already published, or `0` if it wasn't; there's no partial
+    /// result, since validity is only checked on the last slot.
+    ///
+    /// # Safety
+    ///
+    /// Same as `read`: using this concurrently with a `write` touching the same indices is
+    /// technically speaking UB due to data races, for the same reasons documented there.
+    #[cfg(not(feature = "atomic-payload"))]
+    pub unsafe fn read_batch(&self, start_index: usize, out: &mut [mem::MaybeUninit<T>]) -> usize {
+        let len = out.len();
+        let last = match len.checked_sub(1) {
+            Some(last) => last,
+            None => return 0,
+        };
+
+        // Validate the whole run by checking only its last slot with `Acquire`.
+        let last_index = start_index.wrapping_add(last);
+        let last_slot = self.at(last_index);
+        if (*last_slot).index.load(Ordering::Acquire) != last_index {
+            return 0;
+        }
+
+        for (i, out) in out.iter_mut().enumerate() {
+            let index = start_index.wrapping_add(i);
+            let slot = self.at(index);
+            let value = (*slot).data.with(|data| (data as *const T).read_volatile());
+            out.write(value);
+        }
+
+        len
+    }
+
+    /// Reads a contiguous run of up to `out.len()` values starting at `start_index` in one pass,
+    /// validating the whole run with a single `Acquire` load on its last slot instead of one per
+    /// element.
+    ///
+    /// Unlike the default implementation, this copies each slot's payload as a sequence of
+    /// `usize` words instead of a `read_volatile`, the same tradeoff `read`'s `atomic-payload`
+    /// variant makes; see its docs for the requirements on `T` this relies on.
+    ///
+    /// # Safety
+    ///
+    /// Same as `read`'s `atomic-payload` variant: every payload word is loaded atomically, so a
+    /// concurrent `write`/`write_batch` of the same indices is not a data race on `data`.
+    #[cfg(feature = "atomic-payload")]
+    pub unsafe fn read_batch(&self, start_index: usize, out: &mut [mem::MaybeUninit<T>]) -> usize {
+        let len = out.len();
+        let last = match len.checked_sub(1) {
+            Some(last) => last,
+            None => return 0,
+        };
+
+        // Validate the whole run by checking only its last slot with `Acquire`.
+        let last_index = start_index.wrapping_add(last);
+        let last_slot = self.at(last_index);
+        if (*last_slot).index.load(Ordering::Acquire) != last_index {
+            return 0;
+        }
+
+        for (i, out) in out.iter_mut().enumerate() {
+            let index = start_index.wrapping_add(i);
+            let slot = self.at(index);
+            out.write(read_words((*slot).data.get() as *const T));
+        }
+
+        len
+    }
+}
+
+/// Asserts that `write_words`/`read_words` can soundly reinterpret a `T` as `[usize; N]`: both
+/// its size and its alignment must accommodate `usize`. Unlike a `debug_assert`, this runs in
+/// release builds too --- a misaligned `*const/mut AtomicUsize` (e.g. from a `T` like `[u8; 8]`,
+/// whose size passes a size-only check but whose alignment is 1) is immediate UB on dereference,
+/// not just a logic bug, so it can't be left for `debug_assert` to catch only in debug builds.
+#[cfg(feature = "atomic-payload")]
+fn assert_atomic_payload_layout<T>() {
+    assert_eq!(
+        mem::size_of::<T>() % mem::size_of::<usize>(),
+        0,
+        "atomic-payload requires size_of::<T>() to be a multiple of size_of::<usize>()",
+    );
+    assert!(
+        mem::align_of::<T>() >= mem::align_of::<usize>(),
+        "atomic-payload requires align_of::<T>() to be at least align_of::<usize>()",
+    );
+}
+
+/// Copies `value` into `*dst` one `usize` word at a time, each stored with `Relaxed`.
+///
+/// # Safety
+///
+/// `dst` must be valid for writes of a `T`, and `size_of::<T>()`/`align_of::<T>()` must satisfy
+/// [`assert_atomic_payload_layout`].
+#[cfg(feature = "atomic-payload")]
+unsafe fn write_words<T>(dst: *mut T, value: T) {
+    let words = mem::size_of::<T>() / mem::size_of::<usize>();
+    let src = &value as *const T as *const usize;
+    let dst = dst as *mut usize;
+
+    for i in 0..words {
+        let word = src.add(i).read();
+        (*(dst.add(i) as *const AtomicUsize)).store(word, Ordering::Relaxed);
+    }
+}
+
+/// Reads a `T` out of `*src` one `usize` word at a time, each loaded with `Relaxed`.
+///
+/// # Safety
+///
+/// `src` must be valid for reads of a `T`, and `size_of::<T>()`/`align_of::<T>()` must satisfy
+/// [`assert_atomic_payload_layout`].
+#[cfg(feature = "atomic-payload")]
+unsafe fn read_words<T>(src: *const T) -> T {
+    let words = mem::size_of::<T>() / mem::size_of::<usize>();
+    let src = src as *const usize;
+    let mut out = mem::MaybeUninit::<T>::uninit();
+    let dst = out.as_mut_ptr() as *mut usize;
+
+    for i in 0..words {
+        let word = (*(src.add(i) as *const AtomicUsize)).load(Ordering::Relaxed);
+        dst.add(i).write(word);
+    }
+
+    out.assume_init()
 }
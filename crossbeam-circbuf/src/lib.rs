@@ -4,21 +4,43 @@
 //!
 //! - bounded/unbounded SPSC (single-producer single-consumer)
 //! - bounded/unbounded SPMC (single-producer multiple-consumer)
+//!
+//! With the default `std` feature disabled, the crate builds under `#![no_std]` (plus `alloc` for
+//! `Buffer`'s allocation); enable `portable-atomic` on targets without native word-sized CAS. The
+//! `sp` flavors are bounded and available in that configuration; `mp` still requires `std` since
+//! its producer/consumer handles are shared through `std::sync::Arc`.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs, missing_debug_implementations)]
 
-extern crate crossbeam_epoch as epoch;
+// `atomic-payload` reinterprets a slot's `data` cell as a raw `*const/mut T` (see `buffer.rs`'s
+// `write_words`/`read_words`), but loom's shadow `UnsafeCell::get()` returns a tracked `ConstPtr`/
+// `MutPtr` wrapper instead of a raw pointer, so the two don't compose. This combination isn't
+// meaningful anyway: `atomic-payload` exists to bypass loom-checkable synchronization on real
+// hardware, so there's nothing for the model checker to check here.
+#[cfg(all(feature = "loom", loom, feature = "atomic-payload"))]
+compile_error!("the `atomic-payload` feature cannot be combined with `loom`");
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
 extern crate crossbeam_utils as utils;
 
-mod buffer;
+#[doc(hidden)] // for the `Buffer`-level tests and for shared-memory/externally-backed use
+pub mod buffer;
+mod sync;
 
 #[doc(hidden)] // for doc-tests
 pub mod sp;
+#[cfg(feature = "std")]
 #[doc(hidden)] // for doc-tests
 pub mod mp;
 
+pub use buffer::Buffer;
+
 pub use sp::mc as spmc;
 pub use sp::sc as spsc;
+#[cfg(feature = "std")]
 pub use mp::mc as mpmc;
 
 /// The return type for `try_recv` methods.
@@ -30,6 +52,8 @@ pub enum TryRecv<T> {
     Empty,
     /// Lost the race to a concurrent operation. Try again.
     Retry,
+    /// The buffer is empty and the producer has closed the queue; no more data will ever arrive.
+    Closed,
 }
 
 impl<T> TryRecv<T> {
@@ -39,6 +63,56 @@ impl<T> TryRecv<T> {
             TryRecv::Data(v) => TryRecv::Data(f(v)),
             TryRecv::Empty => TryRecv::Empty,
             TryRecv::Retry => TryRecv::Retry,
+            TryRecv::Closed => TryRecv::Closed,
         }
     }
 }
+
+/// The return type for `try_send` methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrySend<T> {
+    /// Sent the value successfully.
+    Sent,
+    /// Didn't send the value because the buffer is full.
+    Full(T),
+    /// Lost the race to a concurrent operation. Try again.
+    Retry(T),
+    /// Didn't send the value because the queue is closed.
+    Closed(T),
+}
+
+impl<T> TrySend<T> {
+    /// Applies a function to the value held by `TrySend::Full`, `TrySend::Retry`, or
+    /// `TrySend::Closed`.
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> TrySend<U> {
+        match self {
+            TrySend::Sent => TrySend::Sent,
+            TrySend::Full(v) => TrySend::Full(f(v)),
+            TrySend::Retry(v) => TrySend::Retry(f(v)),
+            TrySend::Closed(v) => TrySend::Closed(f(v)),
+        }
+    }
+}
+
+/// The return type for `try_send_slice` methods.
+///
+/// Unlike `TrySend`, there's no value to hand back on failure: the input slice itself is left
+/// untouched past whatever prefix was actually sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrySendSlice {
+    /// Sent this many elements, draining a prefix of the input slice. May be fewer than the
+    /// slice's length (including zero) if the queue didn't have room for all of it.
+    Sent(usize),
+    /// Didn't send anything because the queue is closed.
+    Closed,
+}
+
+/// The return type for `try_recv_slice` methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvSlice {
+    /// Received this many elements into the output slice. May be fewer than the slice's length
+    /// (including zero) if the queue doesn't have that many available yet.
+    Received(usize),
+    /// The queue is empty and the producer has closed it; no more data will ever arrive.
+    Closed,
+}
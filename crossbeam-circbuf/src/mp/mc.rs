@@ -0,0 +1,193 @@
+//! A bounded multi-producer multi-consumer queue.
+//!
+//! Both ends are `Clone`: any number of producers may share one queue, as may any number of
+//! consumers. Each side claims its slot with a CAS on a shared counter (`tail` for producers,
+//! `head` for consumers) before touching the slot.
+//!
+//! Unlike `sp::sc`/`sp::mc`, `tail` can no longer be used by consumers to tell whether a slot has
+//! actually been written: with several producers racing, one may claim (and CAS-advance `tail`
+//! past) a slot before another, earlier-claimed slot has finished being written. So `try_recv`
+//! falls back to `Buffer::read`'s own per-slot `Acquire` check for readiness, the same as a
+//! single-producer queue's consumer would use, and treats "claimed but not yet visible" the same
+//! as losing a race: `TryRecv::Retry`.
+
+#[cfg(feature = "std")]
+use std::sync::Arc;
+use core::fmt;
+use core::mem;
+
+use crate::buffer::Buffer;
+use crate::sync::{AtomicBool, AtomicUsize, Ordering};
+use crate::utils::CachePadded;
+use crate::{TryRecv, TrySend};
+
+struct Inner<T> {
+    buffer: Buffer<T>,
+
+    /// Next index to claim for reading. Shared by every `Consumer`; claimed via CAS.
+    head: CachePadded<AtomicUsize>,
+
+    /// Next index to claim for writing. Shared by every `Producer`; claimed via CAS.
+    tail: CachePadded<AtomicUsize>,
+
+    /// Set once any handle calls `close`. No more values will ever arrive once this is set and
+    /// the buffer has been drained.
+    closed: AtomicBool,
+}
+
+/// Creates a bounded MPMC queue with the specified capacity, returning an initial producer and
+/// consumer handle (clone either for more producers/consumers).
+///
+/// `cap` must be a power of two.
+pub fn new<T>(cap: usize) -> (Producer<T>, Consumer<T>) {
+    let inner = Arc::new(Inner {
+        buffer: Buffer::new(cap),
+        head: CachePadded::new(AtomicUsize::new(0)),
+        tail: CachePadded::new(AtomicUsize::new(0)),
+        closed: AtomicBool::new(false),
+    });
+
+    (
+        Producer {
+            inner: inner.clone(),
+        },
+        Consumer { inner },
+    )
+}
+
+/// A producer handle to a bounded MPMC queue. Cloning creates another independent handle racing
+/// for the same slots.
+pub struct Producer<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Producer<T> {
+    /// Attempts to send `value` into the queue, without blocking.
+    pub fn try_send(&self, value: T) -> TrySend<T> {
+        if self.inner.closed.load(Ordering::Relaxed) {
+            return TrySend::Closed(value);
+        }
+
+        let cap = self.inner.buffer.cap();
+        let mut tail = self.inner.tail.load(Ordering::Relaxed);
+
+        loop {
+            let head = self.inner.head.load(Ordering::Acquire);
+
+            if tail.wrapping_sub(head) >= cap {
+                return TrySend::Full(value);
+            }
+
+            match self.inner.tail.compare_exchange_weak(
+                tail,
+                tail.wrapping_add(1),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(t) => tail = t,
+            }
+        }
+
+        unsafe { self.inner.buffer.write(tail, value) };
+        TrySend::Sent
+    }
+
+    /// Closes the queue: once every `Consumer` drains whatever is already in the buffer, their
+    /// `try_recv` will start reporting `TryRecv::Closed` instead of `TryRecv::Empty`, and further
+    /// `try_send` calls fail with `TrySend::Closed`.
+    pub fn close(&self) {
+        self.inner.closed.store(true, Ordering::Release);
+    }
+}
+
+impl<T> Clone for Producer<T> {
+    fn clone(&self) -> Self {
+        Producer {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Producer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Producer { .. }")
+    }
+}
+
+/// A consumer handle to a bounded MPMC queue. Cloning creates another independent handle racing
+/// for the same slots.
+pub struct Consumer<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Consumer<T> {
+    /// Attempts to receive a value from the queue, without blocking.
+    pub fn try_recv(&self) -> TryRecv<T> {
+        let head = self.inner.head.load(Ordering::Relaxed);
+
+        match unsafe { self.inner.buffer.read(head) } {
+            Some(v) => {
+                // Race the other consumers for this slot. On loss, `v` is a raw duplicate of data
+                // the winner already owns and will return; `ManuallyDrop<T>` doesn't drop its
+                // inner `T`, so simply letting `v` fall out of scope here is enough to avoid a
+                // double-drop.
+                if self
+                    .inner
+                    .head
+                    .compare_exchange(
+                        head,
+                        head.wrapping_add(1),
+                        Ordering::Relaxed,
+                        Ordering::Relaxed,
+                    )
+                    .is_ok()
+                {
+                    TryRecv::Data(mem::ManuallyDrop::into_inner(v))
+                } else {
+                    TryRecv::Retry
+                }
+            }
+            None => {
+                let tail = self.inner.tail.load(Ordering::Acquire);
+
+                if head != tail {
+                    // A producer has claimed this slot but hasn't published it yet. Note this
+                    // also means a `close` racing with a final in-flight send can never be
+                    // mistaken for `Closed` here: `head != tail` routes to `Retry`, and the
+                    // caller will see the real data once the send finishes and tries again.
+                    TryRecv::Retry
+                } else if self.inner.closed.load(Ordering::Acquire) {
+                    // A send that finished before `close` may have claimed `tail` after the
+                    // `tail` load above; check once more before giving up.
+                    if self.inner.tail.load(Ordering::Acquire) != head {
+                        TryRecv::Retry
+                    } else {
+                        TryRecv::Closed
+                    }
+                } else {
+                    TryRecv::Empty
+                }
+            }
+        }
+    }
+
+    /// Closes the queue; see [`Producer::close`].
+    pub fn close(&self) {
+        self.inner.closed.store(true, Ordering::Release);
+    }
+}
+
+impl<T> Clone for Consumer<T> {
+    fn clone(&self) -> Self {
+        Consumer {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Consumer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Consumer { .. }")
+    }
+}
@@ -0,0 +1,5 @@
+//! Multi-producer queue flavors.
+//!
+//! `mc` is multi-producer multi-consumer.
+
+pub mod mc;
@@ -0,0 +1,257 @@
+//! A bounded single-producer multi-consumer queue.
+//!
+//! This is the same bounded ring-buffer protocol as `sc`, except `Consumer` is `Clone`: multiple
+//! consumer handles may share one queue, racing to claim each slot with a CAS on `head`.
+
+#[cfg(feature = "std")]
+use std::sync::Arc;
+#[cfg(not(feature = "std"))]
+use alloc::sync::Arc;
+use core::fmt;
+use core::mem;
+
+use crate::buffer::Buffer;
+use crate::sync::{AtomicBool, AtomicUsize, Ordering};
+use crate::utils::CachePadded;
+use crate::{TryRecv, TryRecvSlice, TrySend, TrySendSlice};
+
+struct Inner<T> {
+    buffer: Buffer<T>,
+
+    /// Next index to claim for reading. Shared by every `Consumer`; claimed via CAS.
+    head: CachePadded<AtomicUsize>,
+
+    /// Next index the producer will write to. Only ever written by the (single) producer.
+    tail: CachePadded<AtomicUsize>,
+
+    /// Set once either end calls `close`. No more values will ever arrive once this is set and
+    /// the buffer has been drained.
+    closed: AtomicBool,
+}
+
+/// Creates a bounded SPMC queue with the specified capacity, returning its producer and an
+/// initial consumer handle (clone it for more consumers).
+///
+/// `cap` must be a power of two.
+pub fn new<T>(cap: usize) -> (Producer<T>, Consumer<T>) {
+    let inner = Arc::new(Inner {
+        buffer: Buffer::new(cap),
+        head: CachePadded::new(AtomicUsize::new(0)),
+        tail: CachePadded::new(AtomicUsize::new(0)),
+        closed: AtomicBool::new(false),
+    });
+
+    (
+        Producer {
+            inner: inner.clone(),
+        },
+        Consumer { inner },
+    )
+}
+
+/// The (single) producer end of a bounded SPMC queue.
+pub struct Producer<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Producer<T> {
+    /// Attempts to send `value` into the queue, without blocking.
+    pub fn try_send(&self, value: T) -> TrySend<T> {
+        if self.inner.closed.load(Ordering::Relaxed) {
+            return TrySend::Closed(value);
+        }
+
+        let cap = self.inner.buffer.cap();
+        let tail = self.inner.tail.load(Ordering::Relaxed);
+        let head = self.inner.head.load(Ordering::Acquire);
+
+        if tail.wrapping_sub(head) >= cap {
+            return TrySend::Full(value);
+        }
+
+        unsafe { self.inner.buffer.write(tail, value) };
+        self.inner.tail.store(tail.wrapping_add(1), Ordering::Release);
+        TrySend::Sent
+    }
+
+    /// Attempts to send as many of `values` as there is room for, without blocking.
+    ///
+    /// Drains a prefix of `values` the same way [`Buffer::write_batch`](crate::buffer::Buffer)
+    /// drains its argument: each element actually sent is moved out and left logically
+    /// uninitialized. Returns the number of elements sent, which may be fewer than
+    /// `values.len()` if the queue doesn't have enough room for all of it.
+    pub fn try_send_slice(&self, values: &mut [mem::ManuallyDrop<T>]) -> TrySendSlice {
+        if self.inner.closed.load(Ordering::Relaxed) {
+            return TrySendSlice::Closed;
+        }
+
+        let cap = self.inner.buffer.cap();
+        let tail = self.inner.tail.load(Ordering::Relaxed);
+        let head = self.inner.head.load(Ordering::Acquire);
+
+        let free = cap.wrapping_sub(tail.wrapping_sub(head));
+        let n = values.len().min(free);
+        if n == 0 {
+            return TrySendSlice::Sent(0);
+        }
+
+        // A run starting at `tail` may cross the buffer's `cap` boundary; split it into the two
+        // contiguous sub-runs that straddle the wraparound, same as `Buffer::at`'s masking does
+        // for individual indices.
+        let first_len = n.min(cap - (tail & (cap - 1)));
+        unsafe {
+            self.inner.buffer.write_batch(tail, &mut values[..first_len]);
+            if first_len < n {
+                self.inner
+                    .buffer
+                    .write_batch(tail.wrapping_add(first_len), &mut values[first_len..n]);
+            }
+        }
+
+        self.inner.tail.store(tail.wrapping_add(n), Ordering::Release);
+        TrySendSlice::Sent(n)
+    }
+
+    /// Closes the queue: once every `Consumer` drains whatever is already in the buffer, their
+    /// `try_recv`/`try_recv_slice` will start reporting `TryRecv::Closed`/`TryRecvSlice::Closed`
+    /// instead of `TryRecv::Empty`/`TryRecvSlice::Received(0)`, and further
+    /// `try_send`/`try_send_slice` calls fail with `TrySend::Closed`/`TrySendSlice::Closed`.
+    pub fn close(&self) {
+        self.inner.closed.store(true, Ordering::Release);
+    }
+}
+
+impl<T> fmt::Debug for Producer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Producer { .. }")
+    }
+}
+
+/// A consumer handle to a bounded SPMC queue. Cloning creates another independent handle racing
+/// for the same slots.
+pub struct Consumer<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Consumer<T> {
+    /// Attempts to receive a value from the queue, without blocking.
+    ///
+    /// Returns `TryRecv::Retry` if this handle lost the race for the next slot to a concurrent
+    /// `Consumer`; the caller should simply call `try_recv` again.
+    ///
+    /// Claiming a slot (the CAS on `head`) happens *before* reading it, not after: since `tail`
+    /// is only visible here once it's been `Acquire`-loaded past the claimed index, and the
+    /// (single) producer only publishes `tail` with `Release` after writing that slot, the claim
+    /// is the sole owner of the slot's data by the time it reads it. Reading first and only then
+    /// racing to claim would instead hand every losing consumer its own raw copy of the payload,
+    /// which is unsound for any `T` that isn't `Copy`.
+    pub fn try_recv(&self) -> TryRecv<T> {
+        let head = self.inner.head.load(Ordering::Relaxed);
+        let mut tail = self.inner.tail.load(Ordering::Acquire);
+
+        if head == tail {
+            if !self.inner.closed.load(Ordering::Acquire) {
+                return TryRecv::Empty;
+            }
+
+            // The producer may have published one last value and then closed in between the
+            // `tail` load above and the `closed` load; check once more before giving up.
+            tail = self.inner.tail.load(Ordering::Acquire);
+            if head == tail {
+                return TryRecv::Closed;
+            }
+        }
+
+        match self.inner.head.compare_exchange(
+            head,
+            head.wrapping_add(1),
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        ) {
+            Ok(_) => {
+                let v = unsafe { self.inner.buffer.read_unchecked(head) };
+                TryRecv::Data(mem::ManuallyDrop::into_inner(v))
+            }
+            Err(_) => TryRecv::Retry,
+        }
+    }
+
+    /// Attempts to receive as many values as fit in `out`, without blocking.
+    ///
+    /// Claims the slots it reads the same way `try_recv` claims a single slot: via a CAS on
+    /// `head`, retried in a loop if a concurrent `Consumer` changes `head` first. Returns the
+    /// number of elements received, which may be fewer than `out.len()` if the queue doesn't
+    /// have that many available right now.
+    pub fn try_recv_slice(&self, out: &mut [mem::MaybeUninit<T>]) -> TryRecvSlice {
+        loop {
+            let head = self.inner.head.load(Ordering::Relaxed);
+            let mut tail = self.inner.tail.load(Ordering::Acquire);
+
+            let mut avail = tail.wrapping_sub(head);
+            if avail == 0 {
+                if !self.inner.closed.load(Ordering::Acquire) {
+                    return TryRecvSlice::Received(0);
+                }
+
+                // The producer may have published one last value and then closed in between the
+                // `tail` load above and the `closed` load; check once more before giving up.
+                tail = self.inner.tail.load(Ordering::Acquire);
+                avail = tail.wrapping_sub(head);
+                if avail == 0 {
+                    return TryRecvSlice::Closed;
+                }
+            }
+
+            let n = out.len().min(avail);
+            if n == 0 {
+                return TryRecvSlice::Received(0);
+            }
+
+            if self
+                .inner
+                .head
+                .compare_exchange_weak(
+                    head,
+                    head.wrapping_add(n),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                )
+                .is_err()
+            {
+                continue;
+            }
+
+            let cap = self.inner.buffer.cap();
+            let first_len = n.min(cap - (head & (cap - 1)));
+            unsafe {
+                self.inner.buffer.read_batch(head, &mut out[..first_len]);
+                if first_len < n {
+                    self.inner
+                        .buffer
+                        .read_batch(head.wrapping_add(first_len), &mut out[first_len..n]);
+                }
+            }
+
+            return TryRecvSlice::Received(n);
+        }
+    }
+
+    /// Closes the queue; see [`Producer::close`].
+    pub fn close(&self) {
+        self.inner.closed.store(true, Ordering::Release);
+    }
+}
+
+impl<T> Clone for Consumer<T> {
+    fn clone(&self) -> Self {
+        Consumer {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Consumer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Consumer { .. }")
+    }
+}
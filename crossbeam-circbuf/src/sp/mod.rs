@@ -0,0 +1,6 @@
+//! Single-producer queue flavors.
+//!
+//! `sc` is single-producer single-consumer; `mc` is single-producer multi-consumer.
+
+pub mod mc;
+pub mod sc;
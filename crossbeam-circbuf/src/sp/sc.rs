@@ -0,0 +1,224 @@
+//! A bounded single-producer single-consumer queue.
+
+#[cfg(feature = "std")]
+use std::sync::Arc;
+#[cfg(not(feature = "std"))]
+use alloc::sync::Arc;
+use core::fmt;
+use core::mem;
+
+use crate::buffer::Buffer;
+use crate::sync::{AtomicBool, AtomicUsize, Ordering};
+use crate::utils::CachePadded;
+use crate::{TryRecv, TryRecvSlice, TrySend, TrySendSlice};
+
+struct Inner<T> {
+    buffer: Buffer<T>,
+
+    /// Next index the consumer will read from. Only ever written by the consumer.
+    head: CachePadded<AtomicUsize>,
+
+    /// Next index the producer will write to. Only ever written by the producer.
+    tail: CachePadded<AtomicUsize>,
+
+    /// Set once either end calls `close`. No more values will ever arrive once this is set and
+    /// the buffer has been drained.
+    closed: AtomicBool,
+}
+
+/// Creates a bounded SPSC queue with the specified capacity, returning its producer and consumer
+/// ends.
+///
+/// `cap` must be a power of two.
+pub fn new<T>(cap: usize) -> (Producer<T>, Consumer<T>) {
+    let inner = Arc::new(Inner {
+        buffer: Buffer::new(cap),
+        head: CachePadded::new(AtomicUsize::new(0)),
+        tail: CachePadded::new(AtomicUsize::new(0)),
+        closed: AtomicBool::new(false),
+    });
+
+    (
+        Producer {
+            inner: inner.clone(),
+        },
+        Consumer { inner },
+    )
+}
+
+/// The producer end of a bounded SPSC queue.
+pub struct Producer<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Producer<T> {
+    /// Attempts to send `value` into the queue, without blocking.
+    pub fn try_send(&self, value: T) -> TrySend<T> {
+        if self.inner.closed.load(Ordering::Relaxed) {
+            return TrySend::Closed(value);
+        }
+
+        let cap = self.inner.buffer.cap();
+        let tail = self.inner.tail.load(Ordering::Relaxed);
+        let head = self.inner.head.load(Ordering::Acquire);
+
+        if tail.wrapping_sub(head) >= cap {
+            return TrySend::Full(value);
+        }
+
+        unsafe { self.inner.buffer.write(tail, value) };
+        self.inner.tail.store(tail.wrapping_add(1), Ordering::Release);
+        TrySend::Sent
+    }
+
+    /// Attempts to send as many of `values` as there is room for, without blocking.
+    ///
+    /// Drains a prefix of `values` the same way [`Buffer::write_batch`](crate::buffer::Buffer)
+    /// drains its argument: each element actually sent is moved out and left logically
+    /// uninitialized. Returns the number of elements sent, which may be fewer than
+    /// `values.len()` if the queue doesn't have enough room for all of it.
+    pub fn try_send_slice(&self, values: &mut [mem::ManuallyDrop<T>]) -> TrySendSlice {
+        if self.inner.closed.load(Ordering::Relaxed) {
+            return TrySendSlice::Closed;
+        }
+
+        let cap = self.inner.buffer.cap();
+        let tail = self.inner.tail.load(Ordering::Relaxed);
+        let head = self.inner.head.load(Ordering::Acquire);
+
+        let free = cap.wrapping_sub(tail.wrapping_sub(head));
+        let n = values.len().min(free);
+        if n == 0 {
+            return TrySendSlice::Sent(0);
+        }
+
+        // A run starting at `tail` may cross the buffer's `cap` boundary; split it into the two
+        // contiguous sub-runs that straddle the wraparound, same as `Buffer::at`'s masking does
+        // for individual indices.
+        let first_len = n.min(cap - (tail & (cap - 1)));
+        unsafe {
+            self.inner.buffer.write_batch(tail, &mut values[..first_len]);
+            if first_len < n {
+                self.inner
+                    .buffer
+                    .write_batch(tail.wrapping_add(first_len), &mut values[first_len..n]);
+            }
+        }
+
+        self.inner.tail.store(tail.wrapping_add(n), Ordering::Release);
+        TrySendSlice::Sent(n)
+    }
+
+    /// Closes the queue: once the consumer drains whatever is already in the buffer, its
+    /// `try_recv` will start reporting `TryRecv::Closed` instead of `TryRecv::Empty`, and further
+    /// `try_send`/`try_send_slice` calls fail with `TrySend::Closed`/`TrySendSlice::Closed`.
+    pub fn close(&self) {
+        self.inner.closed.store(true, Ordering::Release);
+    }
+}
+
+impl<T> fmt::Debug for Producer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Producer { .. }")
+    }
+}
+
+/// The consumer end of a bounded SPSC queue.
+pub struct Consumer<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Consumer<T> {
+    /// Attempts to receive a value from the queue, without blocking.
+    pub fn try_recv(&self) -> TryRecv<T> {
+        let head = self.inner.head.load(Ordering::Relaxed);
+
+        match unsafe { self.inner.buffer.read(head) } {
+            Some(v) => {
+                // `Release`, not `Relaxed`: the producer's `Acquire` load of `head` in `try_send`
+                // must happen-after this slot's read completes above, or it could start
+                // overwriting the slot while this read is still in flight.
+                self.inner
+                    .head
+                    .store(head.wrapping_add(1), Ordering::Release);
+                TryRecv::Data(mem::ManuallyDrop::into_inner(v))
+            }
+            None => {
+                if !self.inner.closed.load(Ordering::Acquire) {
+                    return TryRecv::Empty;
+                }
+
+                // The producer may have published one last value and then closed in between our
+                // read attempt above and the `closed` load; check once more before giving up.
+                match unsafe { self.inner.buffer.read(head) } {
+                    Some(v) => {
+                        self.inner
+                            .head
+                            .store(head.wrapping_add(1), Ordering::Release);
+                        TryRecv::Data(mem::ManuallyDrop::into_inner(v))
+                    }
+                    None => TryRecv::Closed,
+                }
+            }
+        }
+    }
+
+    /// Attempts to receive as many values as fit in `out`, without blocking.
+    ///
+    /// Returns the number of elements received, which may be fewer than `out.len()` if the
+    /// queue doesn't have that many available right now.
+    pub fn try_recv_slice(&self, out: &mut [mem::MaybeUninit<T>]) -> TryRecvSlice {
+        let head = self.inner.head.load(Ordering::Relaxed);
+        let mut tail = self.inner.tail.load(Ordering::Acquire);
+
+        let mut avail = tail.wrapping_sub(head);
+        if avail == 0 {
+            if !self.inner.closed.load(Ordering::Acquire) {
+                return TryRecvSlice::Received(0);
+            }
+
+            // The producer may have published one last value and then closed in between our
+            // `tail` load above and the `closed` load; check once more before giving up.
+            tail = self.inner.tail.load(Ordering::Acquire);
+            avail = tail.wrapping_sub(head);
+            if avail == 0 {
+                return TryRecvSlice::Closed;
+            }
+        }
+
+        let n = out.len().min(avail);
+        if n == 0 {
+            return TryRecvSlice::Received(0);
+        }
+
+        let cap = self.inner.buffer.cap();
+        let first_len = n.min(cap - (head & (cap - 1)));
+        unsafe {
+            self.inner.buffer.read_batch(head, &mut out[..first_len]);
+            if first_len < n {
+                self.inner
+                    .buffer
+                    .read_batch(head.wrapping_add(first_len), &mut out[first_len..n]);
+            }
+        }
+
+        // `Release`, matching `try_recv`'s single-element store: the producer's `Acquire` load of
+        // `head` in `try_send`/`try_send_slice` must happen-after these slots' reads complete
+        // above.
+        self.inner
+            .head
+            .store(head.wrapping_add(n), Ordering::Release);
+        TryRecvSlice::Received(n)
+    }
+
+    /// Closes the queue; see [`Producer::close`].
+    pub fn close(&self) {
+        self.inner.closed.store(true, Ordering::Release);
+    }
+}
+
+impl<T> fmt::Debug for Consumer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Consumer { .. }")
+    }
+}
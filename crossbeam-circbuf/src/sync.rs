@@ -0,0 +1,67 @@
+//! Indirection over atomics and interior mutability, so the epoch-and-index protocol in
+//! `buffer.rs` can be run under [Loom](https://github.com/tokio-rs/loom)'s model checker, and so
+//! it can target platforms without native atomic CAS via [`portable-atomic`].
+//!
+//! With the `loom` and `portable-atomic` features both disabled (the default), every item here is
+//! a thin re-export or wrapper of the corresponding `core` type, so there's no overhead compared
+//! to using it directly. Building with `--cfg loom` and the `loom` feature enabled swaps in Loom's shadow
+//! atomics and cell, which intercept every access to explore all legal thread interleavings
+//! instead of running once on real hardware. Enabling the `portable-atomic` feature instead swaps
+//! in `portable_atomic::AtomicUsize`, which falls back to a lock-based emulation on targets (e.g.
+//! `thumbv6m-none-eabi`) that lack a native word-sized CAS.
+//!
+//! [`portable-atomic`]: https://docs.rs/portable-atomic
+
+#[cfg(all(feature = "loom", loom))]
+pub(crate) use loom::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+#[cfg(all(feature = "portable-atomic", not(all(feature = "loom", loom))))]
+pub(crate) use portable_atomic::{AtomicBool, AtomicUsize};
+#[cfg(all(feature = "portable-atomic", not(all(feature = "loom", loom))))]
+pub(crate) use core::sync::atomic::Ordering;
+
+#[cfg(not(any(all(feature = "loom", loom), feature = "portable-atomic")))]
+pub(crate) use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+#[cfg(all(feature = "loom", loom))]
+pub(crate) use loom::cell::UnsafeCell;
+
+/// A stand-in for `loom::cell::UnsafeCell` that wraps `core::cell::UnsafeCell` when not running
+/// under Loom, exposing the same `new`/`get`/`with`/`with_mut` surface either way so callers never
+/// need to branch on `cfg(loom)`.
+#[cfg(not(all(feature = "loom", loom)))]
+#[derive(Debug)]
+pub(crate) struct UnsafeCell<T>(core::cell::UnsafeCell<T>);
+
+#[cfg(not(all(feature = "loom", loom)))]
+impl<T> UnsafeCell<T> {
+    /// Wraps `value` in a new cell.
+    pub(crate) fn new(value: T) -> Self {
+        Self(core::cell::UnsafeCell::new(value))
+    }
+
+    /// Returns a raw pointer to the underlying data.
+    ///
+    /// Only `buffer.rs`'s `atomic-payload` path (`write`/`read`/`write_batch`/`read_batch`) calls
+    /// this directly; the default path goes through `with`/`with_mut` instead.
+    #[cfg_attr(not(feature = "atomic-payload"), allow(dead_code))]
+    pub(crate) fn get(&self) -> *mut T {
+        self.0.get()
+    }
+
+    /// Runs `f` with a pointer to the underlying data, for read access.
+    ///
+    /// Only used by the default (non-`atomic-payload`) `read`/`read_unchecked`/`read_batch`.
+    #[cfg_attr(feature = "atomic-payload", allow(dead_code))]
+    pub(crate) fn with<R>(&self, f: impl FnOnce(*const T) -> R) -> R {
+        f(self.0.get())
+    }
+
+    /// Runs `f` with a pointer to the underlying data, for write access.
+    ///
+    /// Only used by the default (non-`atomic-payload`) `write`/`write_batch`.
+    #[cfg_attr(feature = "atomic-payload", allow(dead_code))]
+    pub(crate) fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+        f(self.0.get())
+    }
+}
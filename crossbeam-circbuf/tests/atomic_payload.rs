@@ -0,0 +1,43 @@
+//! Tests for the `atomic-payload` feature's word-wise copy path on `Buffer`.
+//!
+//! Only compiled when the feature is enabled:
+//!
+//! ```text
+//! cargo test --test atomic_payload --features atomic-payload
+//! ```
+
+#![cfg(feature = "atomic-payload")]
+
+use std::mem;
+
+use crossbeam_circbuf::buffer::Buffer;
+
+#[test]
+fn read_after_write_round_trips_through_word_copies() {
+    let buffer = Buffer::<usize>::new(4);
+
+    unsafe {
+        buffer.write(0, 11);
+        buffer.write(1, 22);
+
+        assert_eq!(buffer.read(0).map(mem::ManuallyDrop::into_inner), Some(11));
+        assert_eq!(buffer.read(1).map(mem::ManuallyDrop::into_inner), Some(22));
+        assert_eq!(buffer.read(2), None);
+    }
+}
+
+#[test]
+fn write_batch_then_read_batch_round_trips_a_run() {
+    let buffer = Buffer::<usize>::new(4);
+
+    let mut values: Vec<mem::ManuallyDrop<usize>> =
+        vec![1, 2, 3].into_iter().map(mem::ManuallyDrop::new).collect();
+    unsafe { buffer.write_batch(0, &mut values) };
+
+    let mut out = [mem::MaybeUninit::<usize>::uninit(); 3];
+    let n = unsafe { buffer.read_batch(0, &mut out) };
+    assert_eq!(n, 3);
+
+    let received: Vec<usize> = out.iter().map(|v| unsafe { v.assume_init() }).collect();
+    assert_eq!(received, vec![1, 2, 3]);
+}
@@ -0,0 +1,49 @@
+//! Tests for `close()`/`Closed` across the spsc, spmc, and mpmc queues.
+
+#[cfg(feature = "std")]
+use crossbeam_circbuf::mpmc;
+use crossbeam_circbuf::{spmc, spsc, TryRecv, TrySend};
+
+#[test]
+fn spsc_drains_before_reporting_closed() {
+    let (producer, consumer) = spsc::new::<usize>(4);
+
+    assert_eq!(producer.try_send(1), TrySend::Sent);
+    producer.close();
+
+    assert_eq!(consumer.try_recv(), TryRecv::Data(1));
+    assert_eq!(consumer.try_recv(), TryRecv::Closed);
+    assert_eq!(producer.try_send(2), TrySend::Closed(2));
+}
+
+#[test]
+fn spsc_consumer_close_is_visible_to_producer() {
+    let (producer, consumer) = spsc::new::<usize>(4);
+
+    consumer.close();
+    assert_eq!(producer.try_send(1), TrySend::Closed(1));
+}
+
+#[test]
+fn spmc_drains_before_reporting_closed() {
+    let (producer, consumer) = spmc::new::<usize>(4);
+
+    assert_eq!(producer.try_send(1), TrySend::Sent);
+    producer.close();
+
+    assert_eq!(consumer.try_recv(), TryRecv::Data(1));
+    assert_eq!(consumer.try_recv(), TryRecv::Closed);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn mpmc_drains_before_reporting_closed() {
+    let (producer, consumer) = mpmc::new::<usize>(4);
+
+    assert_eq!(producer.try_send(1), TrySend::Sent);
+    producer.close();
+
+    assert_eq!(consumer.try_recv(), TryRecv::Data(1));
+    assert_eq!(consumer.try_recv(), TryRecv::Closed);
+    assert_eq!(producer.try_send(2), TrySend::Closed(2));
+}
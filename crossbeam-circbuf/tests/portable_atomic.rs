@@ -0,0 +1,25 @@
+//! Tests that the `portable-atomic` feature doesn't change queue behavior, just the atomic
+//! backend used underneath.
+//!
+//! Only compiled when the feature is enabled:
+//!
+//! ```text
+//! cargo test --test portable_atomic --features portable-atomic
+//! ```
+
+#![cfg(feature = "portable-atomic")]
+
+use crossbeam_circbuf::{spsc, TryRecv, TrySend};
+
+#[test]
+fn spsc_send_and_receive_still_work() {
+    let (producer, consumer) = spsc::new::<usize>(4);
+
+    assert_eq!(producer.try_send(1), TrySend::Sent);
+    assert_eq!(producer.try_send(2), TrySend::Sent);
+    producer.close();
+
+    assert_eq!(consumer.try_recv(), TryRecv::Data(1));
+    assert_eq!(consumer.try_recv(), TryRecv::Data(2));
+    assert_eq!(consumer.try_recv(), TryRecv::Closed);
+}
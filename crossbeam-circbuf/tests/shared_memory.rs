@@ -0,0 +1,49 @@
+//! Tests for `Buffer::from_raw`/`wrap`, the externally-backed buffer constructors meant for
+//! shared-memory/cross-process use.
+
+use std::alloc::{alloc_zeroed, dealloc, Layout};
+use std::mem;
+
+use crossbeam_circbuf::buffer::{Buffer, Slot};
+
+#[test]
+fn wrap_over_a_zeroed_region_round_trips_after_init_invalid() {
+    const CAP: usize = 4;
+
+    let layout = Layout::array::<Slot<usize>>(CAP).unwrap();
+    let ptr = unsafe { alloc_zeroed(layout) };
+    assert!(!ptr.is_null());
+    let region = unsafe { std::slice::from_raw_parts_mut(ptr, layout.size()) };
+
+    unsafe {
+        let buffer = Buffer::<usize>::wrap(region, CAP).unwrap();
+        // A freshly zeroed region isn't a valid empty slot array on its own; `init_invalid` must
+        // run first.
+        buffer.init_invalid();
+
+        buffer.write(0, 42);
+        assert_eq!(buffer.read(0).map(mem::ManuallyDrop::into_inner), Some(42));
+        assert_eq!(buffer.read(1), None);
+    }
+
+    unsafe { dealloc(ptr, layout) };
+}
+
+#[test]
+fn from_raw_over_an_initialized_slot_array_is_reusable_without_reinitializing() {
+    const CAP: usize = 4;
+
+    // `Buffer::new` both allocates and initializes the slot array; hand its raw parts to
+    // `from_raw` the same way a second process attaching to already-initialized shared memory
+    // would, minus the actual cross-process part.
+    let owned = Buffer::<usize>::new(CAP);
+    unsafe { owned.write(0, 7) };
+
+    let ptr = unsafe { owned.at(0) };
+    let overlay = unsafe { Buffer::<usize>::from_raw(ptr, CAP) };
+    assert_eq!(unsafe { overlay.read(0) }.map(mem::ManuallyDrop::into_inner), Some(7));
+
+    // `overlay` doesn't own `ptr`; dropping it must not free `owned`'s allocation.
+    drop(overlay);
+    assert_eq!(unsafe { owned.read(0) }.map(mem::ManuallyDrop::into_inner), Some(7));
+}
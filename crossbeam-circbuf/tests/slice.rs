@@ -0,0 +1,72 @@
+//! Tests for the bulk `try_send_slice`/`try_recv_slice` APIs on the bounded SPSC/SPMC queues.
+
+use std::mem;
+
+use crossbeam_circbuf::{spmc, spsc, TryRecvSlice, TrySendSlice};
+
+fn manually_drop_slice(values: &[usize]) -> Vec<mem::ManuallyDrop<usize>> {
+    values.iter().map(|&v| mem::ManuallyDrop::new(v)).collect()
+}
+
+fn uninit_slice(len: usize) -> Vec<mem::MaybeUninit<usize>> {
+    (0..len).map(|_| mem::MaybeUninit::uninit()).collect()
+}
+
+#[test]
+fn spsc_try_send_slice_partially_fills_when_queue_is_short_on_room() {
+    let (producer, _consumer) = spsc::new::<usize>(4);
+
+    let mut values = manually_drop_slice(&[1, 2, 3, 4, 5, 6]);
+    assert_eq!(producer.try_send_slice(&mut values), TrySendSlice::Sent(4));
+}
+
+#[test]
+fn spsc_try_recv_slice_round_trips_values_in_order() {
+    let (producer, consumer) = spsc::new::<usize>(4);
+
+    let mut values = manually_drop_slice(&[10, 20, 30]);
+    assert_eq!(producer.try_send_slice(&mut values), TrySendSlice::Sent(3));
+
+    let mut out = uninit_slice(3);
+    assert_eq!(consumer.try_recv_slice(&mut out), TryRecvSlice::Received(3));
+    let received: Vec<usize> = out.into_iter().map(|v| unsafe { v.assume_init() }).collect();
+    assert_eq!(received, vec![10, 20, 30]);
+}
+
+#[test]
+fn spsc_try_send_slice_reports_closed() {
+    let (producer, _consumer) = spsc::new::<usize>(4);
+    producer.close();
+
+    let mut values = manually_drop_slice(&[1, 2]);
+    assert_eq!(producer.try_send_slice(&mut values), TrySendSlice::Closed);
+}
+
+#[test]
+fn spsc_try_recv_slice_reports_closed_once_drained() {
+    let (producer, consumer) = spsc::new::<usize>(4);
+
+    let mut values = manually_drop_slice(&[1]);
+    assert_eq!(producer.try_send_slice(&mut values), TrySendSlice::Sent(1));
+    producer.close();
+
+    // The one value sent before closing is still there to drain first.
+    let mut out = uninit_slice(1);
+    assert_eq!(consumer.try_recv_slice(&mut out), TryRecvSlice::Received(1));
+
+    let mut out = uninit_slice(1);
+    assert_eq!(consumer.try_recv_slice(&mut out), TryRecvSlice::Closed);
+}
+
+#[test]
+fn spmc_try_recv_slice_round_trips_values() {
+    let (producer, consumer) = spmc::new::<usize>(4);
+
+    let mut values = manually_drop_slice(&[7, 8]);
+    assert_eq!(producer.try_send_slice(&mut values), TrySendSlice::Sent(2));
+
+    let mut out = uninit_slice(2);
+    assert_eq!(consumer.try_recv_slice(&mut out), TryRecvSlice::Received(2));
+    let received: Vec<usize> = out.into_iter().map(|v| unsafe { v.assume_init() }).collect();
+    assert_eq!(received, vec![7, 8]);
+}